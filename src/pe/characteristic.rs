@@ -73,6 +73,22 @@ let show_type characteristics =
   else "MANY"                   (* print all *)
  */
 use crate::error;
+use std::fmt;
+
+/// Write a set of flag names, as yielded by `Characteristics::iter`/
+/// `DllCharacteristics::iter`, joined with ` | `; `"(none)"` if empty.
+fn display_flags(mut names: impl Iterator<Item = &'static str>, f: &mut fmt::Formatter) -> fmt::Result {
+    match names.next() {
+        Some(first) => {
+            write!(f, "{}", first)?;
+            for name in names {
+                write!(f, " | {}", name)?;
+            }
+            Ok(())
+        }
+        None => write!(f, "(none)"),
+    }
+}
 
 pub const IMAGE_FILE_RELOCS_STRIPPED: u16 = 0x0001;
 pub const IMAGE_FILE_EXECUTABLE_IMAGE: u16 = 0x0002;
@@ -143,3 +159,261 @@ pub fn validate(characteristics: u16, is_image: bool) -> error::Result<()> {
         Err(error::Error::Malformed(error_messages.join("\n")))
     }
 }
+
+const CHARACTERISTIC_NAMES: &[(u16, &str)] = &[
+    (IMAGE_FILE_RELOCS_STRIPPED, "IMAGE_FILE_RELOCS_STRIPPED"),
+    (IMAGE_FILE_EXECUTABLE_IMAGE, "IMAGE_FILE_EXECUTABLE_IMAGE"),
+    (IMAGE_FILE_LINE_NUMS_STRIPPED, "IMAGE_FILE_LINE_NUMS_STRIPPED"),
+    (IMAGE_FILE_LOCAL_SYMS_STRIPPED, "IMAGE_FILE_LOCAL_SYMS_STRIPPED"),
+    (IMAGE_FILE_AGGRESSIVE_WS_TRIM, "IMAGE_FILE_AGGRESSIVE_WS_TRIM"),
+    (IMAGE_FILE_LARGE_ADDRESS_AWARE, "IMAGE_FILE_LARGE_ADDRESS_AWARE"),
+    (RESERVED, "RESERVED"),
+    (IMAGE_FILE_BYTES_REVERSED_LO, "IMAGE_FILE_BYTES_REVERSED_LO"),
+    (IMAGE_FILE_32BIT_MACHINE, "IMAGE_FILE_32BIT_MACHINE"),
+    (IMAGE_FILE_DEBUG_STRIPPED, "IMAGE_FILE_DEBUG_STRIPPED"),
+    (IMAGE_FILE_REMOVABLE_RUN_FROM_SWAP, "IMAGE_FILE_REMOVABLE_RUN_FROM_SWAP"),
+    (IMAGE_FILE_NET_RUN_FROM_SWAP, "IMAGE_FILE_NET_RUN_FROM_SWAP"),
+    (IMAGE_FILE_SYSTEM, "IMAGE_FILE_SYSTEM"),
+    (IMAGE_FILE_DLL, "IMAGE_FILE_DLL"),
+    (IMAGE_FILE_UP_SYSTEM_ONLY, "IMAGE_FILE_UP_SYSTEM_ONLY"),
+    (IMAGE_FILE_BYTES_REVERSED_HI, "IMAGE_FILE_BYTES_REVERSED_HI"),
+];
+
+/// A typed, iterable view of the `IMAGE_FILE_*` characteristics bitflags.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub struct Characteristics(pub u16);
+
+impl Characteristics {
+    pub fn new(characteristics: u16) -> Self {
+        Characteristics(characteristics)
+    }
+
+    pub fn bits(&self) -> u16 {
+        self.0
+    }
+
+    pub fn is_relocs_stripped(&self) -> bool {
+        has_flag(self.0, IMAGE_FILE_RELOCS_STRIPPED)
+    }
+
+    pub fn is_executable_image(&self) -> bool {
+        has_flag(self.0, IMAGE_FILE_EXECUTABLE_IMAGE)
+    }
+
+    pub fn is_line_nums_stripped(&self) -> bool {
+        has_flag(self.0, IMAGE_FILE_LINE_NUMS_STRIPPED)
+    }
+
+    pub fn is_local_syms_stripped(&self) -> bool {
+        has_flag(self.0, IMAGE_FILE_LOCAL_SYMS_STRIPPED)
+    }
+
+    pub fn is_aggressive_ws_trim(&self) -> bool {
+        has_flag(self.0, IMAGE_FILE_AGGRESSIVE_WS_TRIM)
+    }
+
+    pub fn is_large_address_aware(&self) -> bool {
+        has_flag(self.0, IMAGE_FILE_LARGE_ADDRESS_AWARE)
+    }
+
+    pub fn is_bytes_reversed_lo(&self) -> bool {
+        has_flag(self.0, IMAGE_FILE_BYTES_REVERSED_LO)
+    }
+
+    pub fn is_32bit_machine(&self) -> bool {
+        has_flag(self.0, IMAGE_FILE_32BIT_MACHINE)
+    }
+
+    pub fn is_debug_stripped(&self) -> bool {
+        has_flag(self.0, IMAGE_FILE_DEBUG_STRIPPED)
+    }
+
+    pub fn is_removable_run_from_swap(&self) -> bool {
+        has_flag(self.0, IMAGE_FILE_REMOVABLE_RUN_FROM_SWAP)
+    }
+
+    pub fn is_net_run_from_swap(&self) -> bool {
+        has_flag(self.0, IMAGE_FILE_NET_RUN_FROM_SWAP)
+    }
+
+    pub fn is_system(&self) -> bool {
+        has_flag(self.0, IMAGE_FILE_SYSTEM)
+    }
+
+    pub fn is_dll(&self) -> bool {
+        has_flag(self.0, IMAGE_FILE_DLL)
+    }
+
+    pub fn is_up_system_only(&self) -> bool {
+        has_flag(self.0, IMAGE_FILE_UP_SYSTEM_ONLY)
+    }
+
+    pub fn is_bytes_reversed_hi(&self) -> bool {
+        has_flag(self.0, IMAGE_FILE_BYTES_REVERSED_HI)
+    }
+
+    /// Validate the characteristics; see the free function [`validate`].
+    pub fn validate(&self, is_image: bool) -> error::Result<()> {
+        validate(self.0, is_image)
+    }
+
+    /// Iterate over the names of every flag set in `self`
+    pub fn iter(&self) -> impl Iterator<Item = &'static str> {
+        let bits = self.0;
+        CHARACTERISTIC_NAMES.iter()
+            .filter(move |(flag, _)| has_flag(bits, *flag))
+            .map(|(_, name)| *name)
+    }
+}
+
+impl From<u16> for Characteristics {
+    fn from(characteristics: u16) -> Self {
+        Characteristics(characteristics)
+    }
+}
+
+impl fmt::Display for Characteristics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        display_flags(self.iter(), f)
+    }
+}
+
+pub const IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA: u16 = 0x0020;
+pub const IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE: u16 = 0x0040;
+pub const IMAGE_DLLCHARACTERISTICS_FORCE_INTEGRITY: u16 = 0x0080;
+pub const IMAGE_DLLCHARACTERISTICS_NX_COMPAT: u16 = 0x0100;
+pub const IMAGE_DLLCHARACTERISTICS_NO_ISOLATION: u16 = 0x0200;
+pub const IMAGE_DLLCHARACTERISTICS_NO_SEH: u16 = 0x0400;
+pub const IMAGE_DLLCHARACTERISTICS_NO_BIND: u16 = 0x0800;
+pub const IMAGE_DLLCHARACTERISTICS_APPCONTAINER: u16 = 0x1000;
+pub const IMAGE_DLLCHARACTERISTICS_WDM_DRIVER: u16 = 0x2000;
+pub const IMAGE_DLLCHARACTERISTICS_GUARD_CF: u16 = 0x4000;
+pub const IMAGE_DLLCHARACTERISTICS_TERMINAL_SERVER_AWARE: u16 = 0x8000;
+
+/// Bits `0x0001`-`0x0010` of `DllCharacteristics` are reserved and must be zero
+const DLL_CHARACTERISTICS_RESERVED_MASK: u16 = 0x001f;
+
+const DLL_CHARACTERISTIC_NAMES: &[(u16, &str)] = &[
+    (IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA, "IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA"),
+    (IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE, "IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE"),
+    (IMAGE_DLLCHARACTERISTICS_FORCE_INTEGRITY, "IMAGE_DLLCHARACTERISTICS_FORCE_INTEGRITY"),
+    (IMAGE_DLLCHARACTERISTICS_NX_COMPAT, "IMAGE_DLLCHARACTERISTICS_NX_COMPAT"),
+    (IMAGE_DLLCHARACTERISTICS_NO_ISOLATION, "IMAGE_DLLCHARACTERISTICS_NO_ISOLATION"),
+    (IMAGE_DLLCHARACTERISTICS_NO_SEH, "IMAGE_DLLCHARACTERISTICS_NO_SEH"),
+    (IMAGE_DLLCHARACTERISTICS_NO_BIND, "IMAGE_DLLCHARACTERISTICS_NO_BIND"),
+    (IMAGE_DLLCHARACTERISTICS_APPCONTAINER, "IMAGE_DLLCHARACTERISTICS_APPCONTAINER"),
+    (IMAGE_DLLCHARACTERISTICS_WDM_DRIVER, "IMAGE_DLLCHARACTERISTICS_WDM_DRIVER"),
+    (IMAGE_DLLCHARACTERISTICS_GUARD_CF, "IMAGE_DLLCHARACTERISTICS_GUARD_CF"),
+    (IMAGE_DLLCHARACTERISTICS_TERMINAL_SERVER_AWARE, "IMAGE_DLLCHARACTERISTICS_TERMINAL_SERVER_AWARE"),
+];
+
+/// A typed, iterable view of the optional header's `DllCharacteristics`
+/// bitflags (ASLR, CFG, and related loader/linker hints).
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub struct DllCharacteristics(pub u16);
+
+impl DllCharacteristics {
+    pub fn new(dll_characteristics: u16) -> Self {
+        DllCharacteristics(dll_characteristics)
+    }
+
+    pub fn bits(&self) -> u16 {
+        self.0
+    }
+
+    pub fn is_high_entropy_va(&self) -> bool {
+        has_flag(self.0, IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA)
+    }
+
+    pub fn is_dynamic_base(&self) -> bool {
+        has_flag(self.0, IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE)
+    }
+
+    pub fn is_force_integrity(&self) -> bool {
+        has_flag(self.0, IMAGE_DLLCHARACTERISTICS_FORCE_INTEGRITY)
+    }
+
+    pub fn is_nx_compat(&self) -> bool {
+        has_flag(self.0, IMAGE_DLLCHARACTERISTICS_NX_COMPAT)
+    }
+
+    pub fn is_no_isolation(&self) -> bool {
+        has_flag(self.0, IMAGE_DLLCHARACTERISTICS_NO_ISOLATION)
+    }
+
+    pub fn is_no_seh(&self) -> bool {
+        has_flag(self.0, IMAGE_DLLCHARACTERISTICS_NO_SEH)
+    }
+
+    pub fn is_no_bind(&self) -> bool {
+        has_flag(self.0, IMAGE_DLLCHARACTERISTICS_NO_BIND)
+    }
+
+    pub fn is_app_container(&self) -> bool {
+        has_flag(self.0, IMAGE_DLLCHARACTERISTICS_APPCONTAINER)
+    }
+
+    pub fn is_wdm_driver(&self) -> bool {
+        has_flag(self.0, IMAGE_DLLCHARACTERISTICS_WDM_DRIVER)
+    }
+
+    pub fn is_guard_cf(&self) -> bool {
+        has_flag(self.0, IMAGE_DLLCHARACTERISTICS_GUARD_CF)
+    }
+
+    pub fn is_terminal_server_aware(&self) -> bool {
+        has_flag(self.0, IMAGE_DLLCHARACTERISTICS_TERMINAL_SERVER_AWARE)
+    }
+
+    /// Iterate over the names of every flag set in `self`
+    pub fn iter(&self) -> impl Iterator<Item = &'static str> {
+        let bits = self.0;
+        DLL_CHARACTERISTIC_NAMES.iter()
+            .filter(move |(flag, _)| has_flag(bits, *flag))
+            .map(|(_, name)| *name)
+    }
+
+    /// Reject reserved bits (`0x0001`-`0x0010`), which must be zero
+    pub fn validate(&self) -> error::Result<()> {
+        let reserved = self.0 & DLL_CHARACTERISTICS_RESERVED_MASK;
+        if reserved != 0 {
+            Err(error::Error::Malformed(format!("DllCharacteristics has reserved bits set: {:#06x}", reserved)))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl From<u16> for DllCharacteristics {
+    fn from(dll_characteristics: u16) -> Self {
+        DllCharacteristics(dll_characteristics)
+    }
+}
+
+impl fmt::Display for DllCharacteristics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        display_flags(self.iter(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Characteristics, DllCharacteristics, IMAGE_FILE_DLL, IMAGE_FILE_EXECUTABLE_IMAGE};
+
+    #[test]
+    fn characteristics_display_and_iter () {
+        let characteristics = Characteristics::new(IMAGE_FILE_DLL | IMAGE_FILE_EXECUTABLE_IMAGE);
+        assert!(characteristics.is_dll());
+        assert!(characteristics.is_executable_image());
+        assert_eq!(characteristics.to_string(), "IMAGE_FILE_EXECUTABLE_IMAGE | IMAGE_FILE_DLL");
+        assert_eq!(characteristics.iter().count(), 2);
+    }
+
+    #[test]
+    fn dll_characteristics_validate_rejects_reserved_bits () {
+        let valid = DllCharacteristics::new(super::IMAGE_DLLCHARACTERISTICS_NX_COMPAT);
+        assert!(valid.validate().is_ok());
+        let invalid = DllCharacteristics::new(0x1);
+        assert!(invalid.validate().is_err());
+    }
+}
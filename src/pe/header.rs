@@ -1,7 +1,11 @@
 use crate::error;
 
 use crate::pe::optional_header;
-use scroll::Pread;
+use scroll::{Pread, Pwrite};
+use std::ops::Range;
+
+/// Size in bytes of the on-disk `IMAGE_DOS_HEADER`
+pub const SIZEOF_DOS_HEADER: usize = 0x40;
 
 /// DOS header present in all PE binaries
 #[repr(C)]
@@ -9,8 +13,42 @@ use scroll::Pread;
 pub struct DosHeader {
     /// Magic number: 5a4d
     pub signature: u16,
+    /// Bytes on last page of file
+    pub e_cblp: u16,
+    /// Pages in file
+    pub e_cp: u16,
+    /// Relocations
+    pub e_crlc: u16,
+    /// Size of header in paragraphs
+    pub e_cparhdr: u16,
+    /// Minimum extra paragraphs needed
+    pub e_minalloc: u16,
+    /// Maximum extra paragraphs needed
+    pub e_maxalloc: u16,
+    /// Initial (relative) SS value
+    pub e_ss: u16,
+    /// Initial SP value
+    pub e_sp: u16,
+    /// Checksum
+    pub e_csum: u16,
+    /// Initial IP value
+    pub e_ip: u16,
+    /// Initial (relative) CS value
+    pub e_cs: u16,
+    /// File address of relocation table
+    pub e_lfarlc: u16,
+    /// Overlay number
+    pub e_ovno: u16,
+    /// Reserved words
+    pub e_res: [u16; 4],
+    /// OEM identifier
+    pub e_oemid: u16,
+    /// OEM information, specific to `e_oemid`
+    pub e_oeminfo: u16,
+    /// Reserved words
+    pub e_res2: [u16; 10],
     /// Pointer to PE header, always at offset 0x3c
-    pub pe_pointer: u32,
+    pub e_lfanew: u32,
 }
 
 pub const DOS_MAGIC: u16 = 0x5a4d;
@@ -18,11 +56,247 @@ pub const PE_POINTER_OFFSET: u32 = 0x3c;
 
 impl DosHeader {
     pub fn parse(bytes: &[u8]) -> error::Result<Self> {
-        let signature = bytes.pread_with(0, scroll::LE)
-            .map_err(|_| error::Error::Malformed(format!("cannot parse DOS signature (offset {:#x})", 0)))?;
-        let pe_pointer = bytes.pread_with(PE_POINTER_OFFSET as usize, scroll::LE)
-            .map_err(|_| error::Error::Malformed(format!("cannot parse PE header pointer (offset {:#x})", PE_POINTER_OFFSET)))?;
-        Ok (DosHeader { signature: signature, pe_pointer: pe_pointer })
+        let mut offset = 0;
+        let signature = bytes.gread_with(&mut offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse DOS signature (offset {:#x})", offset)))?;
+        let e_cblp = bytes.gread_with(&mut offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse DOS e_cblp (offset {:#x})", offset)))?;
+        let e_cp = bytes.gread_with(&mut offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse DOS e_cp (offset {:#x})", offset)))?;
+        let e_crlc = bytes.gread_with(&mut offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse DOS e_crlc (offset {:#x})", offset)))?;
+        let e_cparhdr = bytes.gread_with(&mut offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse DOS e_cparhdr (offset {:#x})", offset)))?;
+        let e_minalloc = bytes.gread_with(&mut offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse DOS e_minalloc (offset {:#x})", offset)))?;
+        let e_maxalloc = bytes.gread_with(&mut offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse DOS e_maxalloc (offset {:#x})", offset)))?;
+        let e_ss = bytes.gread_with(&mut offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse DOS e_ss (offset {:#x})", offset)))?;
+        let e_sp = bytes.gread_with(&mut offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse DOS e_sp (offset {:#x})", offset)))?;
+        let e_csum = bytes.gread_with(&mut offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse DOS e_csum (offset {:#x})", offset)))?;
+        let e_ip = bytes.gread_with(&mut offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse DOS e_ip (offset {:#x})", offset)))?;
+        let e_cs = bytes.gread_with(&mut offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse DOS e_cs (offset {:#x})", offset)))?;
+        let e_lfarlc = bytes.gread_with(&mut offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse DOS e_lfarlc (offset {:#x})", offset)))?;
+        let e_ovno = bytes.gread_with(&mut offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse DOS e_ovno (offset {:#x})", offset)))?;
+        let mut e_res = [0u16; 4];
+        for res in e_res.iter_mut() {
+            *res = bytes.gread_with(&mut offset, scroll::LE)
+                .map_err(|_| error::Error::Malformed(format!("cannot parse DOS e_res (offset {:#x})", offset)))?;
+        }
+        let e_oemid = bytes.gread_with(&mut offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse DOS e_oemid (offset {:#x})", offset)))?;
+        let e_oeminfo = bytes.gread_with(&mut offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse DOS e_oeminfo (offset {:#x})", offset)))?;
+        let mut e_res2 = [0u16; 10];
+        for res in e_res2.iter_mut() {
+            *res = bytes.gread_with(&mut offset, scroll::LE)
+                .map_err(|_| error::Error::Malformed(format!("cannot parse DOS e_res2 (offset {:#x})", offset)))?;
+        }
+        let e_lfanew: u32 = bytes.gread_with(&mut offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse PE header pointer (offset {:#x})", offset)))?;
+        if e_lfanew as usize >= bytes.len() {
+            return Err(error::Error::Malformed(format!("PE header pointer ({:#x}) is out of bounds (buffer is {:#x} bytes)", e_lfanew, bytes.len())));
+        }
+        Ok(DosHeader {
+            signature: signature,
+            e_cblp: e_cblp,
+            e_cp: e_cp,
+            e_crlc: e_crlc,
+            e_cparhdr: e_cparhdr,
+            e_minalloc: e_minalloc,
+            e_maxalloc: e_maxalloc,
+            e_ss: e_ss,
+            e_sp: e_sp,
+            e_csum: e_csum,
+            e_ip: e_ip,
+            e_cs: e_cs,
+            e_lfarlc: e_lfarlc,
+            e_ovno: e_ovno,
+            e_res: e_res,
+            e_oemid: e_oemid,
+            e_oeminfo: e_oeminfo,
+            e_res2: e_res2,
+            e_lfanew: e_lfanew,
+        })
+    }
+
+    /// The byte range of the DOS stub (e.g. the "This program cannot be run
+    /// in DOS mode." message), which sits between the fixed-size DOS header
+    /// and the PE header pointed to by `e_lfanew`.
+    pub fn dos_stub_range(&self) -> Range<usize> {
+        SIZEOF_DOS_HEADER..(self.e_lfanew as usize)
+    }
+
+    /// Pointer to the PE header, always at offset 0x3c; kept as an alias of
+    /// `e_lfanew` for callers written against the field this module used to expose
+    #[deprecated(since = "0.2.0", note = "use `e_lfanew` instead")]
+    pub fn pe_pointer(&self) -> u32 {
+        self.e_lfanew
+    }
+
+    /// Write the fixed-size `IMAGE_DOS_HEADER` region into `bytes` at `offset`,
+    /// advancing `offset` past it.
+    pub fn write_into(&self, bytes: &mut [u8], offset: &mut usize) -> error::Result<()> {
+        bytes.gwrite_with(self.signature, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write DOS signature (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.e_cblp, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write DOS e_cblp (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.e_cp, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write DOS e_cp (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.e_crlc, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write DOS e_crlc (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.e_cparhdr, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write DOS e_cparhdr (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.e_minalloc, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write DOS e_minalloc (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.e_maxalloc, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write DOS e_maxalloc (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.e_ss, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write DOS e_ss (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.e_sp, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write DOS e_sp (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.e_csum, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write DOS e_csum (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.e_ip, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write DOS e_ip (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.e_cs, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write DOS e_cs (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.e_lfarlc, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write DOS e_lfarlc (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.e_ovno, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write DOS e_ovno (offset {:#x})", offset)))?;
+        for res in self.e_res.iter() {
+            bytes.gwrite_with(*res, offset, scroll::LE)
+                .map_err(|_| error::Error::Malformed(format!("cannot write DOS e_res (offset {:#x})", offset)))?;
+        }
+        bytes.gwrite_with(self.e_oemid, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write DOS e_oemid (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.e_oeminfo, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write DOS e_oeminfo (offset {:#x})", offset)))?;
+        for res in self.e_res2.iter() {
+            bytes.gwrite_with(*res, offset, scroll::LE)
+                .map_err(|_| error::Error::Malformed(format!("cannot write DOS e_res2 (offset {:#x})", offset)))?;
+        }
+        bytes.gwrite_with(self.e_lfanew, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write PE header pointer (offset {:#x})", offset)))?;
+        Ok(())
+    }
+}
+
+/// Magic number marking the start of the (decoded) "Rich" header: `DanS`
+const RICH_DANS_MAGIC: u32 = 0x536e6144;
+/// Magic number marking the end of the "Rich" header: `Rich`
+const RICH_RICH_MAGIC: u32 = 0x68636952;
+
+/// A single toolchain component recorded in the `Rich` header, e.g. a
+/// specific version of the linker, compiler, assembler, or CVTRES used
+/// to produce the binary.
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+pub struct RichEntry {
+    /// Identifies the tool itself, e.g. the linker, the C compiler, etc.
+    pub product_id: u16,
+    /// Identifies the specific build of the tool
+    pub build_id: u16,
+    /// How many times this tool was invoked while producing the binary
+    pub count: u32,
+}
+
+/// The undocumented MSVC "Rich" header that sits between the DOS stub and
+/// the PE signature. It records every Microsoft toolchain component (and
+/// how many times it was invoked) that went into building the binary,
+/// which makes it useful for provenance and forensics.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct RichHeader {
+    /// The toolchain components recorded in the header, in on-disk order
+    pub entries: Vec<RichEntry>,
+    /// The XOR key the header is obfuscated with
+    pub xor_key: u32,
+    /// Whether the recomputed checksum matches `xor_key`; `false` indicates
+    /// the header was tampered with (or miscomputed by a non-MSVC tool)
+    pub checksum_valid: bool,
+}
+
+impl RichHeader {
+    /// Search `bytes` for a `Rich` header located somewhere before `pe_pointer`
+    /// (the start of the PE signature) and parse it.
+    ///
+    /// Returns `Ok(None)` if no `Rich` header is present, which is expected for
+    /// binaries not produced by the MSVC toolchain.
+    pub fn parse(bytes: &[u8], pe_pointer: u32) -> error::Result<Option<Self>> {
+        let search_end = pe_pointer as usize;
+        let mut rich_offset = None;
+        let mut offset = SIZEOF_DOS_HEADER;
+        while offset + 4 <= search_end {
+            let marker: u32 = bytes.pread_with(offset, scroll::LE)
+                .map_err(|_| error::Error::Malformed(format!("cannot parse candidate Rich header marker (offset {:#x})", offset)))?;
+            if marker == RICH_RICH_MAGIC {
+                rich_offset = Some(offset);
+                break;
+            }
+            offset += 4;
+        }
+        let rich_offset = match rich_offset {
+            Some(rich_offset) => rich_offset,
+            None => return Ok(None),
+        };
+        let xor_key: u32 = bytes.pread_with(rich_offset + 4, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse Rich header XOR key (offset {:#x})", rich_offset + 4)))?;
+        let mut decoded = vec![];
+        let mut cursor = rich_offset;
+        let dans_offset = loop {
+            if cursor < 4 {
+                return Err(error::Error::Malformed("Rich header is missing its DanS start marker".to_string()));
+            }
+            cursor -= 4;
+            let encoded: u32 = bytes.pread_with(cursor, scroll::LE)
+                .map_err(|_| error::Error::Malformed(format!("cannot parse Rich header DWORD (offset {:#x})", cursor)))?;
+            let value = encoded ^ xor_key;
+            if value == RICH_DANS_MAGIC {
+                break cursor;
+            }
+            decoded.push(value);
+        };
+        decoded.reverse();
+        // The three DWORDs immediately following `DanS` are padding (zero once decoded)
+        let pairs = if decoded.len() >= 3 { &decoded[3..] } else { &[] };
+        let mut entries = Vec::with_capacity(pairs.len() / 2);
+        for pair in pairs.chunks_exact(2) {
+            let comp_id = pair[0];
+            let count = pair[1];
+            entries.push(RichEntry {
+                product_id: (comp_id >> 16) as u16,
+                build_id: comp_id as u16,
+                count: count,
+            });
+        }
+        let checksum = Self::checksum(bytes, dans_offset, &entries);
+        Ok(Some(RichHeader { entries: entries, xor_key: xor_key, checksum_valid: checksum == xor_key }))
+    }
+
+    /// Recompute the XOR key / checksum the way the MSVC linker does: seed with
+    /// the offset of the `DanS` marker, rotate-left-and-add every preceding DOS
+    /// header byte (skipping the 4 bytes of `e_lfanew` at offset `0x3c`), then
+    /// rotate-left-and-add every `comp_id` by its `count`.
+    fn checksum(bytes: &[u8], dans_offset: usize, entries: &[RichEntry]) -> u32 {
+        let mut checksum = dans_offset as u32;
+        for i in 0..dans_offset {
+            if i >= PE_POINTER_OFFSET as usize && i < PE_POINTER_OFFSET as usize + 4 {
+                continue;
+            }
+            checksum = checksum.wrapping_add((bytes[i] as u32).rotate_left(i as u32));
+        }
+        for entry in entries {
+            let comp_id = ((entry.product_id as u32) << 16) | entry.build_id as u32;
+            checksum = checksum.wrapping_add(comp_id.rotate_left(entry.count));
+        }
+        checksum
     }
 }
 
@@ -48,6 +322,70 @@ pub const COFF_MAGIC: u32 = 0x00004550;
 pub const COFF_MACHINE_X86: u16 = 0x14c;
 pub const COFF_MACHINE_X86_64: u16 = 0x8664;
 
+/// The contents of this file are assumed to be applicable to any machine type
+pub const COFF_MACHINE_UNKNOWN: u16 = 0x0;
+pub const COFF_MACHINE_AM33: u16 = 0x1d3;
+pub const COFF_MACHINE_AMD64: u16 = 0x8664;
+pub const COFF_MACHINE_ARM: u16 = 0x1c0;
+pub const COFF_MACHINE_ARM64: u16 = 0xaa64;
+pub const COFF_MACHINE_ARMNT: u16 = 0x1c4;
+pub const COFF_MACHINE_EBC: u16 = 0xebc;
+pub const COFF_MACHINE_I386: u16 = 0x14c;
+pub const COFF_MACHINE_IA64: u16 = 0x200;
+pub const COFF_MACHINE_LOONGARCH32: u16 = 0x6232;
+pub const COFF_MACHINE_LOONGARCH64: u16 = 0x6264;
+pub const COFF_MACHINE_M32R: u16 = 0x9041;
+pub const COFF_MACHINE_MIPS16: u16 = 0x266;
+pub const COFF_MACHINE_MIPSFPU: u16 = 0x366;
+pub const COFF_MACHINE_MIPSFPU16: u16 = 0x466;
+pub const COFF_MACHINE_POWERPC: u16 = 0x1f0;
+pub const COFF_MACHINE_POWERPCFP: u16 = 0x1f1;
+pub const COFF_MACHINE_R4000: u16 = 0x166;
+pub const COFF_MACHINE_RISCV32: u16 = 0x5032;
+pub const COFF_MACHINE_RISCV64: u16 = 0x5064;
+pub const COFF_MACHINE_RISCV128: u16 = 0x5128;
+pub const COFF_MACHINE_SH3: u16 = 0x1a2;
+pub const COFF_MACHINE_SH3DSP: u16 = 0x1a3;
+pub const COFF_MACHINE_SH4: u16 = 0x1a6;
+pub const COFF_MACHINE_SH5: u16 = 0x1a8;
+pub const COFF_MACHINE_THUMB: u16 = 0x1c2;
+pub const COFF_MACHINE_WCEMIPSV2: u16 = 0x169;
+
+/// Translate an `IMAGE_FILE_MACHINE_*` value into its human-readable name,
+/// e.g. for use in `{:?}` output or other diagnostics. Unrecognized values
+/// yield `"UNKNOWN"`.
+pub fn machine_to_str(machine: u16) -> &'static str {
+    match machine {
+        COFF_MACHINE_AM33 => "AM33",
+        COFF_MACHINE_AMD64 => "AMD64",
+        COFF_MACHINE_ARM => "ARM",
+        COFF_MACHINE_ARM64 => "ARM64",
+        COFF_MACHINE_ARMNT => "ARMNT",
+        COFF_MACHINE_EBC => "EBC",
+        COFF_MACHINE_I386 => "I386",
+        COFF_MACHINE_IA64 => "IA64",
+        COFF_MACHINE_LOONGARCH32 => "LOONGARCH32",
+        COFF_MACHINE_LOONGARCH64 => "LOONGARCH64",
+        COFF_MACHINE_M32R => "M32R",
+        COFF_MACHINE_MIPS16 => "MIPS16",
+        COFF_MACHINE_MIPSFPU => "MIPSFPU",
+        COFF_MACHINE_MIPSFPU16 => "MIPSFPU16",
+        COFF_MACHINE_POWERPC => "POWERPC",
+        COFF_MACHINE_POWERPCFP => "POWERPCFP",
+        COFF_MACHINE_R4000 => "R4000",
+        COFF_MACHINE_RISCV32 => "RISCV32",
+        COFF_MACHINE_RISCV64 => "RISCV64",
+        COFF_MACHINE_RISCV128 => "RISCV128",
+        COFF_MACHINE_SH3 => "SH3",
+        COFF_MACHINE_SH3DSP => "SH3DSP",
+        COFF_MACHINE_SH4 => "SH4",
+        COFF_MACHINE_SH5 => "SH5",
+        COFF_MACHINE_THUMB => "THUMB",
+        COFF_MACHINE_WCEMIPSV2 => "WCEMIPSV2",
+        _ => "UNKNOWN",
+    }
+}
+
 impl CoffHeader {
     pub fn parse(bytes: &[u8], offset: &mut usize) -> error::Result<Self> {
         let mut coff = CoffHeader::default();
@@ -69,11 +407,40 @@ impl CoffHeader {
             .map_err(|_| error::Error::Malformed(format!("cannot parse COFF characteristics (offset {:#x})", offset)))?;
         Ok(coff)
     }
+
+    /// The human-readable name of `self.machine`, e.g. `"AMD64"`
+    pub fn machine_type(&self) -> &'static str {
+        machine_to_str(self.machine)
+    }
+
+    /// Write the `IMAGE_FILE_HEADER` region into `bytes` at `offset`,
+    /// advancing `offset` past it.
+    pub fn write_into(&self, bytes: &mut [u8], offset: &mut usize) -> error::Result<()> {
+        bytes.gwrite_with(self.signature, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write COFF signature (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.machine, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write COFF machine (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.number_of_sections, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write COFF number of sections (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.time_date_stamp, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write COFF time date stamp (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.pointer_to_symbol_table, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write COFF pointer to symbol table (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.number_of_symbol_table, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write COFF number of symbol (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.size_of_optional_header, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write COFF size of optional header (offset {:#x})", offset)))?;
+        bytes.gwrite_with(self.characteristics, offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot write COFF characteristics (offset {:#x})", offset)))?;
+        Ok(())
+    }
 }
 
-#[derive(Debug, PartialEq, Copy, Clone, Default)]
+#[derive(Debug, PartialEq, Clone, Default)]
 pub struct Header {
     pub dos_header: DosHeader,
+    /// The MSVC "Rich" header, if the binary has one
+    pub rich_header: Option<RichHeader>,
     pub coff_header: CoffHeader,
     pub optional_header: Option<optional_header::OptionalHeader>,
 }
@@ -81,14 +448,33 @@ pub struct Header {
 impl Header {
     pub fn parse(bytes: &[u8]) -> error::Result<Self> {
         let dos_header = DosHeader::parse(&bytes)?;
-        let mut offset = dos_header.pe_pointer as usize;
+        let rich_header = RichHeader::parse(&bytes, dos_header.e_lfanew)?;
+        let mut offset = dos_header.e_lfanew as usize;
         let coff_header = CoffHeader::parse(&bytes, &mut offset)?;
         let optional_header =
             if coff_header.size_of_optional_header > 0 {
                 Some (bytes.pread::<optional_header::OptionalHeader>(offset)?)
             }
         else { None };
-        Ok( Header { dos_header: dos_header, coff_header: coff_header, optional_header: optional_header })
+        Ok( Header { dos_header: dos_header, rich_header: rich_header, coff_header: coff_header, optional_header: optional_header })
+    }
+
+    /// Write the DOS and COFF headers back into `bytes`, placed at
+    /// `dos_header.e_lfanew` as usual.
+    ///
+    /// Note that the MSVC "Rich" header, the DOS stub, and the optional
+    /// header (if present) are not touched by this call; only the fixed-size
+    /// `IMAGE_DOS_HEADER` and `IMAGE_FILE_HEADER` regions are (re)written.
+    pub fn write_into(&self, bytes: &mut [u8]) -> error::Result<()> {
+        let mut coff_header = self.coff_header;
+        if self.optional_header.is_none() {
+            coff_header.size_of_optional_header = 0;
+        }
+        let mut offset = 0;
+        self.dos_header.write_into(bytes, &mut offset)?;
+        let mut offset = self.dos_header.e_lfanew as usize;
+        coff_header.write_into(bytes, &mut offset)?;
+        Ok(())
     }
 }
 
@@ -149,4 +535,38 @@ mod tests {
         assert!(header.coff_header.machine == COFF_MACHINE_X86);
         println!("header: {:?}", &header);
     }
+
+    #[test]
+    fn crss_header_round_trip () {
+        let header = Header::parse(&&CRSS_HEADER[..]).unwrap();
+        let mut buf = vec![0u8; CRSS_HEADER.len()];
+        header.write_into(&mut buf).unwrap();
+        assert_eq!(&buf[0..super::SIZEOF_DOS_HEADER], &CRSS_HEADER[0..super::SIZEOF_DOS_HEADER]);
+        let pe = header.dos_header.e_lfanew as usize;
+        assert_eq!(&buf[pe..pe + super::SIZEOF_COFF_HEADER], &CRSS_HEADER[pe..pe + super::SIZEOF_COFF_HEADER]);
+    }
+
+    #[test]
+    fn crss_header_machine_type () {
+        let header = Header::parse(&&CRSS_HEADER[..]).unwrap();
+        assert_eq!(header.coff_header.machine_type(), "I386");
+        assert_eq!(super::machine_to_str(0xaa64), "ARM64");
+        assert_eq!(super::machine_to_str(0xffff), "UNKNOWN");
+    }
+
+    #[test]
+    fn crss_header_dos_header () {
+        let header = Header::parse(&&CRSS_HEADER[..]).unwrap();
+        assert_eq!(header.dos_header.e_lfanew, 0xd0);
+        assert_eq!(header.dos_header.dos_stub_range(), 0x40..0xd0);
+    }
+
+    #[test]
+    fn crss_header_rich_header () {
+        let header = Header::parse(&&CRSS_HEADER[..]).unwrap();
+        let rich_header = header.rich_header.expect("CRSS_HEADER has a Rich header");
+        assert!(rich_header.checksum_valid);
+        assert_eq!(rich_header.entries.len(), 6);
+        assert_eq!(rich_header.entries[0], super::RichEntry { product_id: 1, build_id: 0, count: 0x10 });
+    }
 }
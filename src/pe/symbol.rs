@@ -0,0 +1,187 @@
+use crate::error;
+use crate::pe::header::CoffHeader;
+use scroll::Pread;
+use std::borrow::Cow;
+
+/// Size of a single `IMAGE_SYMBOL` record; each auxiliary record has the same size
+pub const SIZEOF_COFF_SYMBOL: usize = 18;
+
+/// A single `IMAGE_SYMBOL` record from a COFF symbol table.
+///
+/// `name` holds the raw 8-byte on-disk name field, which is either a short
+/// (<= 8 byte) name padded with NULs, or a `{zeroes: u32, offset: u32}` pair
+/// pointing into the COFF string table. Use `SymbolTable::name` to resolve it
+/// into an actual string.
+#[repr(C)]
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+pub struct Symbol {
+    pub name: [u8; 8],
+    pub value: u32,
+    pub section_number: i16,
+    pub symbol_type: u16,
+    pub storage_class: u8,
+    pub number_of_aux_symbols: u8,
+}
+
+impl Symbol {
+    pub fn parse(bytes: &[u8], offset: &mut usize) -> error::Result<Self> {
+        let mut symbol = Symbol::default();
+        let name_bytes = bytes.get(*offset..*offset + 8)
+            .ok_or_else(|| error::Error::Malformed(format!("cannot parse COFF symbol name (offset {:#x})", offset)))?;
+        symbol.name.copy_from_slice(name_bytes);
+        *offset += 8;
+        symbol.value = bytes.gread_with(offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse COFF symbol value (offset {:#x})", offset)))?;
+        symbol.section_number = bytes.gread_with(offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse COFF symbol section number (offset {:#x})", offset)))?;
+        symbol.symbol_type = bytes.gread_with(offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse COFF symbol type (offset {:#x})", offset)))?;
+        symbol.storage_class = bytes.gread_with(offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse COFF symbol storage class (offset {:#x})", offset)))?;
+        symbol.number_of_aux_symbols = bytes.gread_with(offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse COFF symbol number of aux symbols (offset {:#x})", offset)))?;
+        Ok(symbol)
+    }
+
+    /// If the name is a long name, the offset into the COFF string table it
+    /// points to; the on-disk name field stores this as `{zeroes: u32, offset: u32}`,
+    /// where `zeroes` being `0` is what distinguishes a long name from a short one.
+    fn string_table_offset(&self) -> Option<u32> {
+        if self.name[0..4] == [0, 0, 0, 0] {
+            Some(u32::from_le_bytes([self.name[4], self.name[5], self.name[6], self.name[7]]))
+        } else {
+            None
+        }
+    }
+}
+
+/// The COFF symbol table, together with the string table that immediately
+/// follows it, which resolves the long names symbol records may point to.
+#[derive(Debug, Default)]
+pub struct SymbolTable<'a> {
+    symbols: Vec<Symbol>,
+    /// The string table region (the 4-byte size prefix included), so offsets
+    /// read from symbol records can be used as-is
+    strings: &'a [u8],
+}
+
+impl<'a> SymbolTable<'a> {
+    /// Parse the symbol table and its trailing string table, as pointed to by
+    /// `header`'s `pointer_to_symbol_table` and `number_of_symbol_table`.
+    pub fn parse(bytes: &'a [u8], header: &CoffHeader) -> error::Result<Self> {
+        let mut offset = header.pointer_to_symbol_table as usize;
+        let mut remaining = header.number_of_symbol_table as usize;
+        // `remaining` comes straight from the file; cap the capacity hint against
+        // the buffer size so a bogus count can't trigger a huge allocation
+        let mut symbols = Vec::with_capacity(remaining.min(bytes.len() / SIZEOF_COFF_SYMBOL));
+        while remaining > 0 {
+            let symbol = Symbol::parse(bytes, &mut offset)?;
+            offset += symbol.number_of_aux_symbols as usize * SIZEOF_COFF_SYMBOL;
+            remaining = remaining.saturating_sub(1 + symbol.number_of_aux_symbols as usize);
+            symbols.push(symbol);
+        }
+        let string_table_size: u32 = bytes.pread_with(offset, scroll::LE)
+            .map_err(|_| error::Error::Malformed(format!("cannot parse COFF string table size (offset {:#x})", offset)))?;
+        let strings = bytes.get(offset..offset + string_table_size as usize)
+            .ok_or_else(|| error::Error::Malformed(format!("COFF string table (offset {:#x}, size {:#x}) is out of bounds", offset, string_table_size)))?;
+        Ok(SymbolTable { symbols: symbols, strings: strings })
+    }
+
+    /// The parsed symbol records, in on-disk order (auxiliary records are
+    /// skipped, not returned as their own `Symbol`)
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Resolve a symbol's name, following the string table indirection for
+    /// long names. Falls back to a placeholder string if a long name's
+    /// offset is out of bounds, rather than failing outright.
+    pub fn name(&self, symbol: &Symbol) -> Cow<'a, str> {
+        match symbol.string_table_offset() {
+            Some(string_table_offset) => {
+                let string_table_offset = string_table_offset as usize;
+                match self.strings.get(string_table_offset..) {
+                    Some(bytes) => {
+                        let nul = bytes.iter().position(|&byte| byte == 0).unwrap_or(bytes.len());
+                        String::from_utf8_lossy(&bytes[..nul])
+                    }
+                    None => Cow::Owned(format!("<invalid string table offset {:#x}>", string_table_offset)),
+                }
+            }
+            None => {
+                let nul = symbol.name.iter().position(|&byte| byte == 0).unwrap_or(symbol.name.len());
+                Cow::Owned(String::from_utf8_lossy(&symbol.name[..nul]).into_owned())
+            }
+        }
+    }
+
+    /// Iterate over the symbol table, resolving each symbol's name
+    pub fn iter(&self) -> impl Iterator<Item = (Cow<'a, str>, Symbol)> + '_ {
+        self.symbols.iter().map(move |symbol| (self.name(symbol), *symbol))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Symbol, SymbolTable};
+    use crate::pe::header::CoffHeader;
+
+    #[test]
+    fn short_name_symbol () {
+        // `.text\0\0\0`, value=0, section_number=1, type=0, storage_class=3 (static), 0 aux symbols
+        let bytes: [u8; 18] = [
+            0x2e, 0x74, 0x65, 0x78, 0x74, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x01, 0x00,
+            0x00, 0x00,
+            0x03,
+            0x00,
+        ];
+        let mut offset = 0;
+        let symbol = Symbol::parse(&bytes, &mut offset).unwrap();
+        assert_eq!(offset, 18);
+        assert_eq!(symbol.section_number, 1);
+        assert_eq!(symbol.storage_class, 3);
+        let table = SymbolTable { symbols: vec![symbol], strings: &[0, 0, 0, 0] };
+        assert_eq!(table.name(&symbol).as_ref(), ".text");
+    }
+
+    #[test]
+    fn long_name_symbol () {
+        // zeroes=0, offset=4 into the string table
+        let bytes: [u8; 18] = [
+            0x00, 0x00, 0x00, 0x00,
+            0x04, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00,
+            0x00, 0x00,
+            0x02,
+            0x00,
+        ];
+        let mut offset = 0;
+        let symbol = Symbol::parse(&bytes, &mut offset).unwrap();
+        // size prefix (4 bytes) + "foo\0" + "a_long_symbol_name\0"
+        let strings: &[u8] = b"\x00\x00\x00\x00foo\0a_long_symbol_name\0";
+        let table = SymbolTable { symbols: vec![symbol], strings: strings };
+        assert_eq!(table.name(&symbol).as_ref(), "foo");
+    }
+
+    #[test]
+    fn skips_aux_symbols () {
+        let coff = CoffHeader { pointer_to_symbol_table: 0, number_of_symbol_table: 2, ..CoffHeader::default() };
+        let mut bytes = vec![];
+        // primary symbol with one aux record
+        bytes.extend_from_slice(&[0x66, 0x6f, 0x6f, 0, 0, 0, 0, 0]); // "foo\0\0\0\0\0"
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend_from_slice(&0i16.to_le_bytes());
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+        bytes.push(0x2); // storage class
+        bytes.push(1); // 1 aux symbol
+        bytes.extend_from_slice(&[0u8; 18]); // aux record, skipped
+        // string table with no extra strings
+        bytes.extend_from_slice(&4u32.to_le_bytes());
+        let table = SymbolTable::parse(&bytes, &coff).unwrap();
+        assert_eq!(table.symbols().len(), 1);
+        assert_eq!(table.name(&table.symbols()[0]).as_ref(), "foo");
+    }
+}